@@ -15,7 +15,9 @@ use core::ptr::NonNull;
 ///
 /// For bytes area, 'count' records number of allocations.
 /// When it goes down to ZERO, free bytes-used area.
-/// For pages area, it will never be freed!
+/// For pages area, freed runs are pushed onto an intrusive freelist (or
+/// merged into `p_pos` when adjacent) and reused by later `alloc_pages`
+/// calls, so the area shrinks in-place instead of never being freed.
 ///
 pub struct EarlyAllocator<const PAGE_SIZE: usize> {
     start: usize,
@@ -23,6 +25,36 @@ pub struct EarlyAllocator<const PAGE_SIZE: usize> {
     b_pos: usize,
     p_pos: usize,
     count: usize,
+    /// Start of the most recently allocated byte block, if any is still live.
+    last_alloc: Option<usize>,
+    /// Head of the intrusive freelist of freed page runs, or `None` if empty.
+    page_free_list: Option<usize>,
+    /// Number of pages currently sitting in `page_free_list`.
+    free_pages: usize,
+    /// Number of unused guard pages left between consecutive bump-path page
+    /// allocations, to trap overruns. See [`Self::set_guard_pages`].
+    guard_pages: usize,
+}
+
+/// Header written into the first two words of a freed page run, linking it into
+/// `EarlyAllocator::page_free_list`.
+///
+/// `next == 0` is used as the end-of-list sentinel: address 0 is never a
+/// valid page run in this allocator, since `start` is always above it.
+#[repr(C)]
+struct FreeRun {
+    next: usize,
+    num_pages: usize,
+}
+
+impl FreeRun {
+    fn next_option(&self) -> Option<usize> {
+        if self.next == 0 {
+            None
+        } else {
+            Some(self.next)
+        }
+    }
 }
 
 impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
@@ -33,6 +65,86 @@ impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
             b_pos: 0,
             p_pos: 0,
             count: 0,
+            last_alloc: None,
+            page_free_list: None,
+            free_pages: 0,
+            guard_pages: 0,
+        }
+    }
+
+    /// Sets the number of unused guard pages to leave between consecutive
+    /// page allocations made on the bump-path (i.e. not served from the
+    /// freelist).
+    ///
+    /// An overrun past the end of an allocated run then walks into the
+    /// guard gap rather than into the next allocation. For this to actually
+    /// trap anything, the guard region must be left unmapped by the MMU
+    /// setup layer once paging is online.
+    pub fn set_guard_pages(&mut self, n: usize) {
+        self.guard_pages = n;
+    }
+
+    /// Allocates `num_pages` contiguous pages like [`PageAllocator::alloc_pages`],
+    /// but zero-fills the returned run first.
+    ///
+    /// Early page-table and DMA-buffer setup almost always needs zeroed
+    /// memory, so this saves callers from repeating their own memset loop
+    /// over the freshly allocated run.
+    pub fn alloc_pages_zeroed(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        let start = self.alloc_pages(num_pages, align_pow2)?;
+        unsafe {
+            core::ptr::write_bytes(start as *mut u8, 0, num_pages * Self::PAGE_SIZE);
+        }
+        Ok(start)
+    }
+
+    /// Returns the bounds `(b_pos, p_pos)` of the still-free avail-area.
+    ///
+    /// Firmware calls this once the permanent bytes- and pages-allocators
+    /// are ready, to `init` them over the leftover space this allocator
+    /// never touched, completing the two-phase boot memory handoff.
+    pub fn into_free_region(&self) -> (usize, usize) {
+        (self.b_pos, self.p_pos)
+    }
+
+    /// Force-rewinds the bytes area back to empty, ignoring `count`.
+    ///
+    /// Useful once the permanent byte allocator is online and the early
+    /// bytes arena can be reclaimed outright instead of waiting for its
+    /// last allocation to be freed.
+    pub fn reset_bytes(&mut self) {
+        self.b_pos = self.start;
+        self.count = 0;
+        self.last_alloc = None;
+    }
+
+    /// Reads the freelist header stored at `addr`.
+    ///
+    /// # Safety
+    /// `addr` must point to a live node of `page_free_list`.
+    unsafe fn read_free_run(addr: usize) -> FreeRun {
+        (addr as *const FreeRun).read()
+    }
+
+    /// Writes a freelist header at `addr`.
+    ///
+    /// # Safety
+    /// `addr` must be the start of a page run of at least
+    /// `size_of::<FreeRun>()` bytes that is no longer referenced elsewhere.
+    unsafe fn write_free_run(addr: usize, run: FreeRun) {
+        (addr as *mut FreeRun).write(run);
+    }
+
+    /// Repoints `prev`'s `next` link (or the freelist head, if `prev` is
+    /// `None`) at `new_next`.
+    fn relink_free_list(&mut self, prev: Option<usize>, new_next: Option<usize>) {
+        match prev {
+            Some(p) => unsafe {
+                let mut run = Self::read_free_run(p);
+                run.next = new_next.unwrap_or(0);
+                Self::write_free_run(p, run);
+            },
+            None => self.page_free_list = new_next,
         }
     }
 }
@@ -45,12 +157,47 @@ impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
         self.b_pos = self.start;
         self.p_pos = self.end;
         self.count = 0;
+        self.last_alloc = None;
+        self.page_free_list = None;
+        self.free_pages = 0;
     }
 
     /// Add a free memory region to the allocator.
+    ///
+    /// Only regions immediately contiguous with the current arena can be
+    /// absorbed: one that starts right where the arena currently `end`s
+    /// grows the backward pages area, and one that ends right where the
+    /// arena currently `start`s grows the forward bytes area. Anything else
+    /// is rejected, mirroring how firmware hands over discovered memory in
+    /// a handful of adjoining chunks rather than one upfront block.
+    ///
+    /// Growing a side shifts its live bump pointer along with its boundary,
+    /// so it's only sound while that side hasn't handed out anything yet
+    /// (`p_pos == end` for the pages side, `b_pos == start` for the bytes
+    /// side) — otherwise the shift would raise/lower the pointer into
+    /// memory that's already live, and the call is rejected instead.
     fn add_memory(&mut self, start: usize, size: usize) -> AllocResult {
-        // Do nothing
-        return Ok(());
+        if size == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+
+        if start == self.end {
+            if self.p_pos != self.end {
+                return Err(AllocError::InvalidParam);
+            }
+            self.end += size;
+            self.p_pos += size;
+        } else if start + size == self.start {
+            if self.b_pos != self.start {
+                return Err(AllocError::InvalidParam);
+            }
+            self.start -= size;
+            self.b_pos -= size;
+        } else {
+            return Err(AllocError::InvalidParam);
+        }
+
+        Ok(())
     }
 }
 
@@ -73,16 +220,28 @@ impl<const PAGE_SIZE: usize> ByteAllocator for EarlyAllocator<PAGE_SIZE> {
 
         self.b_pos = alloc_end;
         self.count += 1;
+        self.last_alloc = Some(alloc_start);
 
         unsafe { Ok(NonNull::new_unchecked(alloc_start as *mut u8)) }
     }
 
     /// Deallocate memory at the given position, size, and alignment.
-    fn dealloc(&mut self, _pos: NonNull<u8>, _layout: Layout) {
+    fn dealloc(&mut self, pos: NonNull<u8>, layout: Layout) {
         if self.count > 0 {
             self.count -= 1;
+
+            // If this was the most-recently-allocated block, we can reclaim
+            // its space immediately by rewinding `b_pos`, even though other
+            // allocations are still live.
+            let pos = pos.as_ptr() as usize;
+            if self.last_alloc == Some(pos) && pos + layout.size() == self.b_pos {
+                self.b_pos = pos;
+                self.last_alloc = None;
+            }
+
             if self.count == 0 {
                 self.b_pos = self.start;
+                self.last_alloc = None;
             }
         }
     }
@@ -116,9 +275,46 @@ impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
         let align = align_pow2 * Self::PAGE_SIZE;
         let alloc_size = num_pages * Self::PAGE_SIZE;
 
-        let alloc_start = (self.p_pos - alloc_size) & !(align - 1);
+        // First-fit scan of the freelist, splitting a larger run if needed,
+        // before falling back to bumping `p_pos` downward.
+        let mut prev = None;
+        let mut cur = self.page_free_list;
+        while let Some(node) = cur {
+            let run = unsafe { Self::read_free_run(node) };
+
+            if node % align == 0 && run.num_pages >= num_pages {
+                if run.num_pages == num_pages {
+                    self.relink_free_list(prev, run.next_option());
+                } else {
+                    let remainder = node + alloc_size;
+                    let remainder_run = FreeRun {
+                        next: run.next,
+                        num_pages: run.num_pages - num_pages,
+                    };
+                    unsafe { Self::write_free_run(remainder, remainder_run) };
+                    self.relink_free_list(prev, Some(remainder));
+                }
+                self.free_pages -= num_pages;
+                return Ok(node);
+            }
+
+            prev = Some(node);
+            cur = run.next_option();
+        }
+
+        // Leave a dead guard gap below the previously allocated run so an
+        // overrun out of this one walks into unmapped address space instead
+        // of into that run. There's no previous run to guard against yet on
+        // the very first bump allocation, so skip the gap then.
+        let guard_size = if self.p_pos == self.end {
+            0
+        } else {
+            self.guard_pages * Self::PAGE_SIZE
+        };
+        let gapped_p_pos = self.p_pos.saturating_sub(guard_size);
+        let alloc_start = gapped_p_pos.checked_sub(alloc_size).ok_or(AllocError::NoMemory)? & !(align - 1);
 
-        if alloc_start < self.b_pos || alloc_start.checked_add(alloc_size).unwrap_or(0) > self.p_pos {
+        if alloc_start < self.b_pos || alloc_start.checked_add(alloc_size).unwrap_or(0) > gapped_p_pos {
             return Err(AllocError::NoMemory);
         }
 
@@ -129,7 +325,21 @@ impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
 
     /// Deallocate contiguous memory pages with given position and count.
     fn dealloc_pages(&mut self, pos: usize, num_pages: usize) {
-        // Do nothing
+        // Coalesce with the backward-growing pages region directly, instead
+        // of freelisting, whenever the freed run is the one currently
+        // sitting right at `p_pos` (i.e. it was the top-most allocation).
+        if pos == self.p_pos {
+            self.p_pos = pos + num_pages * Self::PAGE_SIZE;
+            return;
+        }
+
+        let run = FreeRun {
+            next: self.page_free_list.unwrap_or(0),
+            num_pages,
+        };
+        unsafe { Self::write_free_run(pos, run) };
+        self.page_free_list = Some(pos);
+        self.free_pages += num_pages;
     }
 
     /// Returns the total number of memory pages.
@@ -138,8 +348,11 @@ impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
     }
 
     /// Returns the number of allocated memory pages.
+    ///
+    /// This also counts any guard pages reserved by [`Self::set_guard_pages`],
+    /// since `p_pos` is bumped past them and they are never handed out.
     fn used_pages(&self) -> usize {
-        (self.end - self.p_pos) / Self::PAGE_SIZE
+        (self.end - self.p_pos) / Self::PAGE_SIZE - self.free_pages
     }
 
     /// Returns the number of available memory pages.